@@ -0,0 +1,185 @@
+//! Pluggable API authentication.
+//!
+//! The built-in [`MultiKeyAuth`] accepts any of several configured keys via
+//! either the `api_key` query parameter or an `Authorization: Bearer <key>`
+//! header, using constant-time comparison. Operators who need something
+//! fancier (per-key scopes, an external auth service, ...) can implement
+//! [`ApiAuth`] and store it in `AppState` instead.
+
+use async_trait::async_trait;
+use axum::http::{header, HeaderMap, StatusCode};
+use std::collections::HashMap;
+use subtle::ConstantTimeEq;
+
+/// Result of a successful authorization check.
+#[derive(Debug, Clone)]
+pub(crate) struct AuthContext {
+    /// Label identifying which key matched, safe to log — never the secret itself.
+    pub label: Option<String>,
+}
+
+/// Authorizes an incoming request. Implementations must not leak secrets
+/// through errors or logs; only an opaque label identifying the matched
+/// credential should be surfaced via [`AuthContext`].
+#[async_trait]
+pub(crate) trait ApiAuth: Send + Sync {
+    async fn authorize(
+        &self,
+        headers: &HeaderMap,
+        query: &HashMap<String, String>,
+    ) -> Result<AuthContext, StatusCode>;
+}
+
+struct ApiKeyEntry {
+    label: String,
+    bytes: Vec<u8>,
+}
+
+/// Default [`ApiAuth`]: accepts any of several configured keys, letting
+/// operators rotate/revoke individual keys without affecting the others.
+/// When constructed with no keys, every request is authorized (matches the
+/// proxy's original behavior when no key is configured at all).
+pub(crate) struct MultiKeyAuth {
+    keys: Vec<ApiKeyEntry>,
+}
+
+impl MultiKeyAuth {
+    pub(crate) fn new(keys: Vec<String>) -> Self {
+        let keys = keys
+            .into_iter()
+            .enumerate()
+            .map(|(i, key)| ApiKeyEntry {
+                label: format!("key#{}", i + 1),
+                bytes: key.into_bytes(),
+            })
+            .collect();
+
+        Self { keys }
+    }
+
+    /// Constant-time comparison against every configured key; returns the
+    /// label of the first match, if any.
+    fn matching_label(&self, candidate: &str) -> Option<&str> {
+        let candidate_bytes = candidate.as_bytes();
+        self.keys
+            .iter()
+            .find(|entry| {
+                entry.bytes.len() == candidate_bytes.len()
+                    && bool::from(entry.bytes.ct_eq(candidate_bytes))
+            })
+            .map(|entry| entry.label.as_str())
+    }
+}
+
+fn bearer_token(headers: &HeaderMap) -> Option<String> {
+    headers
+        .get(header::AUTHORIZATION)
+        .and_then(|v| v.to_str().ok())
+        .and_then(|v| v.strip_prefix("Bearer "))
+        .map(str::to_string)
+}
+
+#[async_trait]
+impl ApiAuth for MultiKeyAuth {
+    async fn authorize(
+        &self,
+        headers: &HeaderMap,
+        query: &HashMap<String, String>,
+    ) -> Result<AuthContext, StatusCode> {
+        if self.keys.is_empty() {
+            return Ok(AuthContext { label: None });
+        }
+
+        let candidate = query.get("api_key").cloned().or_else(|| bearer_token(headers));
+
+        let candidate = candidate.ok_or(StatusCode::FORBIDDEN)?;
+
+        match self.matching_label(&candidate) {
+            Some(label) => Ok(AuthContext { label: Some(label.to_string()) }),
+            None => Err(StatusCode::FORBIDDEN),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use axum::http::HeaderValue;
+
+    fn query(pairs: &[(&str, &str)]) -> HashMap<String, String> {
+        pairs.iter().map(|(k, v)| (k.to_string(), v.to_string())).collect()
+    }
+
+    fn bearer_headers(token: &str) -> HeaderMap {
+        let mut headers = HeaderMap::new();
+        headers.insert(
+            header::AUTHORIZATION,
+            HeaderValue::from_str(&format!("Bearer {}", token)).unwrap(),
+        );
+        headers
+    }
+
+    #[tokio::test]
+    async fn no_keys_configured_allows_every_request() {
+        let auth = MultiKeyAuth::new(vec![]);
+        let ctx = auth.authorize(&HeaderMap::new(), &query(&[])).await.unwrap();
+        assert_eq!(ctx.label, None);
+    }
+
+    #[tokio::test]
+    async fn rejects_missing_credential() {
+        let auth = MultiKeyAuth::new(vec!["secretkey1234567".to_string()]);
+        let err = auth.authorize(&HeaderMap::new(), &query(&[])).await.unwrap_err();
+        assert_eq!(err, StatusCode::FORBIDDEN);
+    }
+
+    #[tokio::test]
+    async fn accepts_matching_query_param() {
+        let auth = MultiKeyAuth::new(vec!["secretkey1234567".to_string()]);
+        let ctx = auth
+            .authorize(&HeaderMap::new(), &query(&[("api_key", "secretkey1234567")]))
+            .await
+            .unwrap();
+        assert_eq!(ctx.label.as_deref(), Some("key#1"));
+    }
+
+    #[tokio::test]
+    async fn accepts_matching_bearer_header() {
+        let auth = MultiKeyAuth::new(vec!["secretkey1234567".to_string()]);
+        let ctx = auth
+            .authorize(&bearer_headers("secretkey1234567"), &query(&[]))
+            .await
+            .unwrap();
+        assert_eq!(ctx.label.as_deref(), Some("key#1"));
+    }
+
+    #[tokio::test]
+    async fn query_param_takes_priority_over_bearer_header() {
+        let auth = MultiKeyAuth::new(vec!["keyone1234567890".to_string(), "keytwo1234567890".to_string()]);
+        let ctx = auth
+            .authorize(&bearer_headers("keytwo1234567890"), &query(&[("api_key", "keyone1234567890")]))
+            .await
+            .unwrap();
+        assert_eq!(ctx.label.as_deref(), Some("key#1"));
+    }
+
+    #[tokio::test]
+    async fn rejects_unrecognized_key() {
+        let auth = MultiKeyAuth::new(vec!["secretkey1234567".to_string()]);
+        let err = auth
+            .authorize(&HeaderMap::new(), &query(&[("api_key", "wrongkey")]))
+            .await
+            .unwrap_err();
+        assert_eq!(err, StatusCode::FORBIDDEN);
+    }
+
+    #[tokio::test]
+    async fn labels_identify_the_matching_key_by_position() {
+        let auth = MultiKeyAuth::new(vec!["keyone1234567890".to_string(), "keytwo1234567890".to_string()]);
+        let ctx = auth
+            .authorize(&HeaderMap::new(), &query(&[("api_key", "keytwo1234567890")]))
+            .await
+            .unwrap();
+        assert_eq!(ctx.label.as_deref(), Some("key#2"));
+    }
+}
@@ -0,0 +1,127 @@
+//! Negotiated response compression for the JSON addon endpoints (manifest,
+//! stream list). Stream lists for popular titles can run to tens of
+//! kilobytes of repetitive text, so compressing them measurably helps on
+//! constrained mobile links. Never applied to `proxy_handler`'s media
+//! stream, which must stay byte-identical to upstream for Range support.
+
+use axum::http::{header, HeaderMap, HeaderValue};
+use flate2::write::{DeflateEncoder, GzEncoder};
+use flate2::Compression;
+use std::io::Write;
+
+/// Content codings this proxy knows how to produce.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) enum Encoding {
+    Gzip,
+    Deflate,
+}
+
+impl Encoding {
+    fn as_str(self) -> &'static str {
+        match self {
+            Encoding::Gzip => "gzip",
+            Encoding::Deflate => "deflate",
+        }
+    }
+}
+
+/// Pick the best encoding the client's `Accept-Encoding` header allows, if
+/// any, preferring gzip. Doesn't implement full quality-value negotiation
+/// (RFC 7231 §5.3.4); a coding explicitly marked `q=0` is treated as
+/// unacceptable, anything else listed is treated as acceptable.
+pub(crate) fn negotiate(headers: &HeaderMap) -> Option<Encoding> {
+    let raw = headers.get(header::ACCEPT_ENCODING)?.to_str().ok()?;
+
+    let accepts = |coding: &str| {
+        raw.split(',').any(|part| {
+            let mut segments = part.split(';');
+            let name = segments.next().unwrap_or("").trim();
+            name.eq_ignore_ascii_case(coding)
+                && !segments.any(|p| p.trim().eq_ignore_ascii_case("q=0"))
+        })
+    };
+
+    if accepts("gzip") {
+        Some(Encoding::Gzip)
+    } else if accepts("deflate") {
+        Some(Encoding::Deflate)
+    } else {
+        None
+    }
+}
+
+/// Compress `body` with the given encoding. Uses the fastest compression
+/// level, trading ratio for CPU/memory to preserve the crate's low-footprint
+/// profile rather than chasing the smallest possible payload.
+pub(crate) fn compress(encoding: Encoding, body: &[u8]) -> std::io::Result<Vec<u8>> {
+    match encoding {
+        Encoding::Gzip => {
+            let mut encoder = GzEncoder::new(Vec::new(), Compression::fast());
+            encoder.write_all(body)?;
+            encoder.finish()
+        }
+        Encoding::Deflate => {
+            let mut encoder = DeflateEncoder::new(Vec::new(), Compression::fast());
+            encoder.write_all(body)?;
+            encoder.finish()
+        }
+    }
+}
+
+pub(crate) fn content_encoding_header(encoding: Encoding) -> HeaderValue {
+    HeaderValue::from_static(encoding.as_str())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn headers_with(accept_encoding: &str) -> HeaderMap {
+        let mut headers = HeaderMap::new();
+        headers.insert(header::ACCEPT_ENCODING, HeaderValue::from_str(accept_encoding).unwrap());
+        headers
+    }
+
+    #[test]
+    fn prefers_gzip_when_both_accepted() {
+        assert_eq!(negotiate(&headers_with("gzip, deflate")), Some(Encoding::Gzip));
+    }
+
+    #[test]
+    fn falls_back_to_deflate() {
+        assert_eq!(negotiate(&headers_with("deflate")), Some(Encoding::Deflate));
+    }
+
+    #[test]
+    fn none_when_neither_accepted() {
+        assert_eq!(negotiate(&headers_with("br")), None);
+    }
+
+    #[test]
+    fn none_when_header_missing() {
+        assert_eq!(negotiate(&HeaderMap::new()), None);
+    }
+
+    #[test]
+    fn respects_q_zero() {
+        assert_eq!(negotiate(&headers_with("gzip;q=0, deflate")), Some(Encoding::Deflate));
+    }
+
+    #[test]
+    fn gzip_roundtrips() {
+        let compressed = compress(Encoding::Gzip, b"hello world").unwrap();
+        let mut decoder = flate2::read::GzDecoder::new(&compressed[..]);
+        let mut out = String::new();
+        std::io::Read::read_to_string(&mut decoder, &mut out).unwrap();
+        assert_eq!(out, "hello world");
+    }
+
+    #[test]
+    fn deflate_roundtrips() {
+        let compressed = compress(Encoding::Deflate, b"hello world").unwrap();
+        let mut decoder = flate2::read::DeflateDecoder::new(&compressed[..]);
+        let mut out = String::new();
+        std::io::Read::read_to_string(&mut decoder, &mut out).unwrap();
+        assert_eq!(out, "hello world");
+    }
+}
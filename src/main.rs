@@ -9,6 +9,9 @@
 //! - **URL Caching**: Resolved Real-Debrid URLs cached to improve loading times
 //! - **Security**: API key authentication, SSRF protection, path traversal prevention
 //! - **Performance**: Ultra-low memory footprint (1-5MB), fast startup (<1ms)
+//! - **Observability**: Prometheus metrics at `/metrics` for cache effectiveness,
+//!   upstream health, and proxy throughput
+//! - **Compression**: Negotiated gzip/deflate for the JSON addon endpoints
 //!
 //! # Security
 //!
@@ -17,6 +20,12 @@
 //! - Path sanitization (directory traversal prevention)
 //! - Log sanitization (sensitive data protection)
 
+mod auth;
+mod compression;
+mod proxy_protocol;
+mod range;
+mod telemetry;
+
 // Use mimalloc for better memory efficiency
 use mimalloc::MiMalloc;
 
@@ -24,20 +33,21 @@ use mimalloc::MiMalloc;
 static GLOBAL: MiMalloc = MiMalloc;
 
 use axum::{
-    extract::{Path, Query, Request, State},
+    extract::{connect_info::ConnectInfo, Path, Query, Request, State},
     http::{header, HeaderMap, HeaderValue, StatusCode},
     middleware::{self, Next},
     response::{IntoResponse, Response},
     routing::get,
-    Json, Router,
+    Router,
 };
-use futures::StreamExt;
+use futures::{Stream, StreamExt};
 use moka::future::Cache;
 use moka::sync::Cache as SyncCache;
+use moka::Expiry;
+use metrics_exporter_prometheus::{PrometheusBuilder, PrometheusHandle};
 use serde::{Deserialize, Serialize};
-use std::time::Duration;
+use std::time::{Duration, Instant, SystemTime};
 use std::{collections::HashMap, sync::Arc};
-use subtle::ConstantTimeEq;
 use tokio::sync::Mutex;
 use tokio::time::timeout;
 use tower_http::cors::{Any, CorsLayer};
@@ -45,8 +55,9 @@ use url::Url;
 
 const TIMEOUT_DURATION: Duration = Duration::from_secs(5 * 60); // 5 minutes
 const MAX_CACHE_SIZE: u64 = 1000; // Limit cache to prevent unbounded growth
-const CACHE_TTL: Duration = Duration::from_secs(3600); // 1 hour TTL for cached URLs
+const CACHE_TTL: Duration = Duration::from_secs(3600); // Default TTL when upstream sends no caching hints
 const LOCK_CACHE_TTL: Duration = Duration::from_secs(300); // 5 minutes TTL for locks
+const MAX_REDIRECTS: u8 = 5; // Hop limit while following chained Torrentio/Real-Debrid redirects
 
 // Whitelisted Torrentio domains to prevent SSRF
 const ALLOWED_TORRENTIO_DOMAINS: &[&str] = &[
@@ -55,17 +66,123 @@ const ALLOWED_TORRENTIO_DOMAINS: &[&str] = &[
     "torrentio-debrid.cloud",
 ];
 
+// A resolved Real-Debrid URL together with the validators needed to revalidate
+// it later (RFC 7232 conditional requests) without re-running full resolution.
+#[derive(Clone)]
+struct CachedResolution {
+    url: String,
+    etag: Option<String>,
+    last_modified: Option<String>,
+    // Set when upstream sent `Cache-Control: no-cache`: the entry is kept around
+    // but must be revalidated with a conditional HEAD before being served again.
+    must_revalidate: bool,
+    // Content-Length observed while resolving, used to validate/serve Range requests.
+    content_length: Option<u64>,
+}
+
+// Cache value paired with the per-entry TTL it should expire after, so the
+// `Expiry` policy below can honor whatever lifetime upstream signaled.
+#[derive(Clone)]
+struct CachedEntry {
+    resolution: CachedResolution,
+    ttl: Duration,
+}
+
+// Per-entry expiration policy driven by the upstream Cache-Control/Expires
+// headers captured in `CachedEntry::ttl`, instead of a single cache-wide TTL.
+struct UrlCacheExpiry;
+
+impl Expiry<String, CachedEntry> for UrlCacheExpiry {
+    fn expire_after_create(
+        &self,
+        _key: &String,
+        value: &CachedEntry,
+        _created_at: Instant,
+    ) -> Option<Duration> {
+        Some(value.ttl)
+    }
+}
+
+// Parsed `Cache-Control` directives relevant to resolved-URL caching.
+#[derive(Default)]
+struct CacheControlDirectives {
+    no_store: bool,
+    no_cache: bool,
+    max_age: Option<Duration>,
+}
+
+fn parse_cache_control(value: &str) -> CacheControlDirectives {
+    let mut directives = CacheControlDirectives::default();
+
+    for part in value.split(',') {
+        let mut kv = part.trim().splitn(2, '=');
+        let name = kv.next().unwrap_or("").trim().to_ascii_lowercase();
+
+        match name.as_str() {
+            "no-store" => directives.no_store = true,
+            "no-cache" => directives.no_cache = true,
+            "max-age" => {
+                if let Some(raw) = kv.next() {
+                    if let Ok(secs) = raw.trim().trim_matches('"').parse::<u64>() {
+                        directives.max_age = Some(Duration::from_secs(secs));
+                    }
+                }
+            }
+            _ => {}
+        }
+    }
+
+    directives
+}
+
+// Derive a per-entry cache lifetime from upstream caching headers: honor
+// `Cache-Control: max-age`, fall back to `Expires`, and fall back again to the
+// default `CACHE_TTL` when upstream gives no hint at all. Returns
+// `(ttl, no_store, must_revalidate)`.
+fn compute_cache_ttl(headers: &HeaderMap) -> (Duration, bool, bool) {
+    if let Some(raw) = headers.get(header::CACHE_CONTROL).and_then(|v| v.to_str().ok()) {
+        let directives = parse_cache_control(raw);
+
+        if directives.no_store {
+            return (Duration::from_secs(0), true, false);
+        }
+
+        if let Some(max_age) = directives.max_age {
+            return (max_age, false, directives.no_cache);
+        }
+
+        if directives.no_cache {
+            return (CACHE_TTL, false, true);
+        }
+    }
+
+    if let Some(raw) = headers.get(header::EXPIRES).and_then(|v| v.to_str().ok()) {
+        if let Ok(expires_at) = httpdate::parse_http_date(raw) {
+            return match expires_at.duration_since(SystemTime::now()) {
+                Ok(ttl) => (ttl, false, false),
+                Err(_) => (Duration::from_secs(0), true, false), // already expired
+            };
+        }
+    }
+
+    (CACHE_TTL, false, false)
+}
+
 // Application state
 #[derive(Clone)]
 struct AppState {
     torrentio_url: String,
     torrentio_base_url: String, // Base URL extracted from torrentio_url (e.g., "https://torrentio.strem.fun")
     proxy_server_url: String,
-    api_key: Option<Vec<u8>>, // Store as bytes for constant-time comparison
-    resolved_url_cache: Cache<String, String>,
+    api_auth: Arc<dyn auth::ApiAuth>,
+    // The key embedded into stream URLs handed back to Stremio, if any (the
+    // first configured key; `ApiAuth` only verifies, it doesn't hand out keys).
+    primary_api_key: Option<String>,
+    resolved_url_cache: Cache<String, CachedEntry>,
     // Per-key locks to prevent thundering herd on cache misses (with TTL to prevent memory leak)
     resolve_locks: SyncCache<String, Arc<Mutex<()>>>,
     http_client: reqwest::Client,
+    metrics_handle: PrometheusHandle,
 }
 
 // Configuration from environment variables
@@ -73,8 +190,9 @@ struct Config {
     torrentio_url: String,
     torrentio_base_url: String,
     proxy_server_url: String,
-    api_key: Option<Vec<u8>>,
+    api_keys: Vec<String>,
     port: u16,
+    proxy_protocol: Option<proxy_protocol::ProxyProtocolMode>,
 }
 
 // Stremio manifest structure
@@ -130,16 +248,25 @@ async fn main() -> anyhow::Result<()> {
     // Load and validate configuration
     let config = load_config()?;
 
-    if config.api_key.is_some() {
+    if !config.api_keys.is_empty() {
         tracing::info!(
-            "API_KEY is set. All requests will require it as an 'api_key=your_key' URL query parameter."
+            "{} API key(s) configured. All requests will require one as an 'api_key=your_key' \
+             URL query parameter or an 'Authorization: Bearer your_key' header.",
+            config.api_keys.len()
         );
     }
 
-    // Create LRU cache with TTL
+    let primary_api_key = config.api_keys.first().cloned();
+    let api_auth: Arc<dyn auth::ApiAuth> = Arc::new(auth::MultiKeyAuth::new(config.api_keys));
+
+    let metrics_handle = PrometheusBuilder::new()
+        .install_recorder()
+        .map_err(|e| anyhow::anyhow!("Failed to install Prometheus recorder: {}", e))?;
+
+    // Create LRU cache whose per-entry TTL is derived from upstream caching headers
     let cache = Cache::builder()
         .max_capacity(MAX_CACHE_SIZE)
-        .time_to_live(CACHE_TTL)
+        .expire_after(UrlCacheExpiry)
         .build();
 
     // Create lock cache with TTL to prevent memory leak
@@ -153,7 +280,8 @@ async fn main() -> anyhow::Result<()> {
         torrentio_url: config.torrentio_url,
         torrentio_base_url: config.torrentio_base_url,
         proxy_server_url: config.proxy_server_url,
-        api_key: config.api_key,
+        api_auth,
+        primary_api_key,
         resolved_url_cache: cache,
         resolve_locks: lock_cache,
         http_client: reqwest::Client::builder()
@@ -162,17 +290,23 @@ async fn main() -> anyhow::Result<()> {
             .pool_idle_timeout(Duration::from_secs(30))
             .http1_only() // Disable HTTP/2 to save memory
             .build()?,
+        metrics_handle,
     };
 
-    // Build the router
-    let app = Router::new()
+    // Build the router. `/metrics` is deliberately outside the api_key_middleware
+    // layer: it's an operational endpoint for scrapers, not addon traffic.
+    let authenticated_routes = Router::new()
         .route("/manifest.json", get(manifest_handler))
         .route("/stream/:type/:id.json", get(stream_handler))
         .route("/resolve/realdebrid/*path", get(proxy_handler))
         .layer(middleware::from_fn_with_state(
             state.clone(),
             api_key_middleware,
-        ))
+        ));
+
+    let app = Router::new()
+        .merge(authenticated_routes)
+        .route("/metrics", get(metrics_handler))
         .layer(middleware::from_fn(logging_middleware))
         .layer(
             CorsLayer::new()
@@ -187,7 +321,20 @@ async fn main() -> anyhow::Result<()> {
     let listener = tokio::net::TcpListener::bind(&addr).await?;
     tracing::info!("Addon server is running on {}", addr);
 
-    axum::serve(listener, app).await?;
+    match config.proxy_protocol {
+        Some(mode) => {
+            tracing::info!("PROXY protocol ({:?}) decoding enabled on the TCP listener", mode);
+            let listener = proxy_protocol::ProxyProtocolListener::new(listener, mode);
+            axum::serve(
+                listener,
+                app.into_make_service_with_connect_info::<proxy_protocol::RealClientAddr>(),
+            )
+            .await?;
+        }
+        None => {
+            axum::serve(listener, app).await?;
+        }
+    }
 
     Ok(())
 }
@@ -198,26 +345,7 @@ fn load_config() -> anyhow::Result<Config> {
         .and_then(|p| p.parse().ok())
         .unwrap_or(13470);
 
-    let api_key = std::env::var("API_KEY").ok().and_then(|key| {
-        // Validate API key encoding and characters
-        if key.is_empty() {
-            tracing::warn!("API_KEY is empty, ignoring");
-            return None;
-        }
-
-        // Ensure key doesn't contain problematic characters
-        if key.contains(|c: char| c.is_control() || c.is_whitespace()) {
-            tracing::error!("API_KEY contains invalid characters (control chars or whitespace)");
-            std::process::exit(1);
-        }
-
-        // Warn if key is too short (potential security issue)
-        if key.len() < 16 {
-            tracing::warn!("API_KEY is shorter than 16 characters. Consider using a longer key for better security.");
-        }
-
-        Some(key.into_bytes()) // Convert to bytes for constant-time comparison
-    });
+    let api_keys = load_api_keys();
 
     // Normalize and validate TORRENTIO_URL
     let torrentio_url = std::env::var("TORRENTIO_URL")
@@ -261,15 +389,61 @@ fn load_config() -> anyhow::Result<Config> {
             "PROXY_SERVER_URL must be defined. This should be the publicly accessible URL where this proxy runs (e.g., https://your-domain.com or http://your-ip:13470)"
         ))?;
 
+    let proxy_protocol = std::env::var("PROXY_PROTOCOL")
+        .ok()
+        .filter(|v| !v.is_empty())
+        .map(|v| v.parse())
+        .transpose()?;
+
     Ok(Config {
         torrentio_url,
         torrentio_base_url,
         proxy_server_url,
-        api_key,
+        api_keys,
         port,
+        proxy_protocol,
     })
 }
 
+/// Load and validate API keys from `API_KEYS` (comma-separated, preferred) or
+/// the legacy single-key `API_KEY` variable. Returns an empty list when
+/// neither is set, meaning authentication is disabled.
+fn load_api_keys() -> Vec<String> {
+    let raw = std::env::var("API_KEYS")
+        .ok()
+        .or_else(|| std::env::var("API_KEY").ok());
+
+    let Some(raw) = raw else {
+        return Vec::new();
+    };
+
+    raw.split(',')
+        .map(str::trim)
+        .filter(|key| !key.is_empty())
+        .enumerate()
+        .filter_map(|(i, key)| {
+            // Ensure key doesn't contain problematic characters
+            if key.contains(|c: char| c.is_control() || c.is_whitespace()) {
+                tracing::error!(
+                    "API key #{} contains invalid characters (control chars or whitespace)",
+                    i + 1
+                );
+                std::process::exit(1);
+            }
+
+            // Warn if key is too short (potential security issue)
+            if key.len() < 16 {
+                tracing::warn!(
+                    "API key #{} is shorter than 16 characters. Consider using a longer key for better security.",
+                    i + 1
+                );
+            }
+
+            Some(key.to_string())
+        })
+        .collect()
+}
+
 /// Validate that the Torrentio URL points to a whitelisted domain (SSRF protection)
 fn validate_torrentio_url(url_str: &str) -> anyhow::Result<()> {
     let url = Url::parse(url_str)
@@ -324,13 +498,25 @@ fn sanitize_uri_for_logging(uri: &axum::http::Uri) -> String {
     uri.path().to_string()
 }
 
+/// The true client address, recovered from a PROXY protocol header when
+/// `PROXY_PROTOCOL` is enabled; `None` otherwise (the TCP peer address is then
+/// whatever sits in front of us, e.g. the load balancer).
+fn real_client_addr(req: &Request) -> Option<std::net::SocketAddr> {
+    req.extensions()
+        .get::<ConnectInfo<proxy_protocol::RealClientAddr>>()
+        .map(|ConnectInfo(addr)| addr.0)
+}
+
 // Logging middleware
 async fn logging_middleware(req: Request, next: Next) -> Response {
     // Only log in debug mode to save allocations
     if cfg!(debug_assertions) {
         let method = req.method().clone();
         let uri = req.uri().clone();
-        tracing::info!("{} {}", method, uri);
+        match real_client_addr(&req) {
+            Some(addr) => tracing::info!("{} {} (client: {})", method, uri, addr),
+            None => tracing::info!("{} {}", method, uri),
+        }
     }
 
     next.run(req).await
@@ -340,46 +526,76 @@ async fn logging_middleware(req: Request, next: Next) -> Response {
 async fn api_key_middleware(
     State(state): State<AppState>,
     Query(params): Query<HashMap<String, String>>,
+    headers: HeaderMap,
     req: Request,
     next: Next,
 ) -> Result<Response, StatusCode> {
-    if let Some(expected_key) = &state.api_key {
-        let sanitized_uri = sanitize_uri_for_logging(req.uri());
-        match params.get("api_key") {
-            Some(provided_key) => {
-                // Constant-time comparison to prevent timing attacks
-                let provided_bytes = provided_key.as_bytes();
-
-                // Ensure both keys have the same length before comparison
-                if provided_bytes.len() != expected_key.len() {
-                    tracing::warn!("Access Denied: Incorrect api_key length. Path: {}", sanitized_uri);
-                    return Err(StatusCode::FORBIDDEN);
-                }
+    match state.api_auth.authorize(&headers, &params).await {
+        Ok(ctx) => {
+            if let Some(label) = ctx.label {
+                tracing::debug!("Authorized via {}", label);
+            }
+            Ok(next.run(req).await)
+        }
+        Err(status) => {
+            let sanitized_uri = sanitize_uri_for_logging(req.uri());
+            let client = real_client_addr(&req)
+                .map(|addr| addr.to_string())
+                .unwrap_or_else(|| "unknown".to_string());
+            tracing::warn!("Access Denied. Path: {}. Client: {}", sanitized_uri, client);
+            Err(status)
+        }
+    }
+}
 
-                let is_valid = provided_bytes.ct_eq(expected_key).into();
+// Prometheus exposition endpoint
+async fn metrics_handler(State(state): State<AppState>) -> impl IntoResponse {
+    (
+        [(header::CONTENT_TYPE, "text/plain; version=0.0.4")],
+        state.metrics_handle.render(),
+    )
+}
 
-                if is_valid {
-                    // API key is correct, continue
-                    Ok(next.run(req).await)
-                } else {
-                    tracing::warn!("Access Denied: Incorrect api_key. Path: {}", sanitized_uri);
-                    Err(StatusCode::FORBIDDEN)
-                }
+// Serialize `value` to JSON and, when the client's `Accept-Encoding` allows
+// it, compress the body with gzip/deflate. Only used for the JSON addon
+// endpoints (manifest, stream list) — never for `proxy_handler`'s media
+// stream, which must stay untouched for Range support.
+fn json_response<T: Serialize>(headers: &HeaderMap, value: &T) -> Response {
+    let body = match serde_json::to_vec(value) {
+        Ok(body) => body,
+        Err(e) => {
+            tracing::error!("Failed to serialize JSON response: {}", e);
+            return StatusCode::INTERNAL_SERVER_ERROR.into_response();
+        }
+    };
+
+    let mut response_headers = HeaderMap::new();
+    response_headers.insert(header::CONTENT_TYPE, HeaderValue::from_static("application/json"));
+    response_headers.insert(header::VARY, HeaderValue::from_static("Accept-Encoding"));
+
+    let body = match compression::negotiate(headers) {
+        Some(encoding) => match compression::compress(encoding, &body) {
+            Ok(compressed) => {
+                response_headers.insert(
+                    header::CONTENT_ENCODING,
+                    compression::content_encoding_header(encoding),
+                );
+                compressed
             }
-            None => {
-                tracing::warn!("Access Denied: Missing api_key. Path: {}", sanitized_uri);
-                Err(StatusCode::FORBIDDEN)
+            Err(e) => {
+                tracing::warn!("Failed to compress response, sending uncompressed: {}", e);
+                body
             }
-        }
-    } else {
-        // No API key required
-        Ok(next.run(req).await)
-    }
+        },
+        None => body,
+    };
+
+    (response_headers, body).into_response()
 }
 
 // Manifest endpoint
-async fn manifest_handler() -> Json<Manifest> {
-    Json(Manifest {
+async fn manifest_handler(headers: HeaderMap) -> Response {
+    let manifest = Manifest {
         id: "org.custom.torrentio-debrid-proxy".to_string(),
         version: "1.0.0".to_string(),
         name: "Torrentio Debrid Proxy".to_string(),
@@ -389,14 +605,17 @@ async fn manifest_handler() -> Json<Manifest> {
         resources: vec!["stream".to_string()],
         catalogs: vec![],
         id_prefixes: vec!["tt".to_string()],
-    })
+    };
+
+    json_response(&headers, &manifest)
 }
 
 // Stream metadata endpoint
 async fn stream_handler(
     State(state): State<AppState>,
     Path((stream_type, id)): Path<(String, String)>,
-) -> Result<Json<StreamsOutput>, StatusCode> {
+    headers: HeaderMap,
+) -> Result<Response, StatusCode> {
     tracing::debug!("Processing stream request: {} {}", stream_type, id);
 
     let api_url = format!("{}/stream/{}/{}.json", state.torrentio_url, stream_type, id);
@@ -427,23 +646,18 @@ async fn stream_handler(
                     .url
                     .replace(&state.torrentio_base_url, &state.proxy_server_url);
 
-                if let Some(api_key) = &state.api_key {
+                if let Some(api_key) = &state.primary_api_key {
                     let separator = if stream.url.contains('?') { "&" } else { "?" };
-                    // Convert bytes back to string for URL encoding
-                    if let Ok(key_str) = std::str::from_utf8(api_key) {
-                        stream.url.push_str(&format!(
-                            "{}api_key={}",
-                            separator,
-                            urlencoding::encode(key_str)
-                        ));
-                    }
+                    stream
+                        .url
+                        .push_str(&format!("{}api_key={}", separator, urlencoding::encode(api_key)));
                 }
             }
             stream
         })
         .collect();
 
-    Ok(Json(StreamsOutput { streams }))
+    Ok(json_response(&headers, &StreamsOutput { streams }))
 }
 
 // Stream proxy endpoint
@@ -476,18 +690,29 @@ async fn try_proxy_stream_with_fallback(
     );
 
     // Check cache first
-    if let Some(cached_url) = state.resolved_url_cache.get(&remote_path).await {
-        match try_fetch_and_proxy(&state, &cached_url, range_header.clone(), false).await {
-            Ok(response) => return Ok(response),
-            Err(StatusCode::NOT_FOUND) => {
-                tracing::warn!(
-                    "Cached RD URL returned 404. Retrying without cache: {}",
-                    remote_path
-                );
-                state.resolved_url_cache.invalidate(&remote_path).await;
-                // Continue to resolve fresh URL
+    let cached_entry = state.resolved_url_cache.get(&remote_path).await;
+    telemetry::record_cache_result(cached_entry.is_some());
+    if let Some(entry) = cached_entry {
+        if let Some(resolution) = use_cached_resolution(&state, &remote_path, entry).await {
+            match try_fetch_and_proxy(
+                &state,
+                &resolution.url,
+                range_header.clone(),
+                resolution.content_length,
+            )
+            .await
+            {
+                Ok(response) => return Ok(response),
+                Err(StatusCode::NOT_FOUND) => {
+                    tracing::warn!(
+                        "Cached RD URL returned 404. Retrying without cache: {}",
+                        remote_path
+                    );
+                    state.resolved_url_cache.invalidate(&remote_path).await;
+                    // Continue to resolve fresh URL
+                }
+                Err(e) => return Err(e),
             }
-            Err(e) => return Err(e),
         }
     }
 
@@ -496,69 +721,270 @@ async fn try_proxy_stream_with_fallback(
         .resolve_locks
         .get_with(remote_path.clone(), || Arc::new(Mutex::new(())));
 
-    let _guard = lock.lock().await;
+    let _guard = match lock.try_lock() {
+        Ok(guard) => guard,
+        Err(_) => {
+            telemetry::record_lock_contention();
+            lock.lock().await
+        }
+    };
 
     // Double-check cache after acquiring lock (another request might have populated it)
-    if let Some(cached_url) = state.resolved_url_cache.get(&remote_path).await {
-        match try_fetch_and_proxy(&state, &cached_url, range_header.clone(), false).await {
-            Ok(response) => return Ok(response),
-            Err(StatusCode::NOT_FOUND) => {
-                state.resolved_url_cache.invalidate(&remote_path).await;
-                // Continue to resolve fresh URL
+    if let Some(entry) = state.resolved_url_cache.get(&remote_path).await {
+        if let Some(resolution) = use_cached_resolution(&state, &remote_path, entry).await {
+            match try_fetch_and_proxy(
+                &state,
+                &resolution.url,
+                range_header.clone(),
+                resolution.content_length,
+            )
+            .await
+            {
+                Ok(response) => return Ok(response),
+                Err(StatusCode::NOT_FOUND) => {
+                    state.resolved_url_cache.invalidate(&remote_path).await;
+                    // Continue to resolve fresh URL
+                }
+                Err(e) => return Err(e),
             }
-            Err(e) => return Err(e),
         }
     }
 
     // Resolve URL from Torrentio
-    let new_url = resolve_rd_url(&state, &torrentio_url, &remote_path)
-        .await
-        .ok_or_else(|| {
-            tracing::error!("Failed to resolve stream URL");
-            StatusCode::BAD_GATEWAY
-        })?;
+    let resolve_started_at = Instant::now();
+    let resolution = resolve_rd_url(&state, &torrentio_url, &remote_path).await;
+    telemetry::record_resolve_duration(resolve_started_at.elapsed());
+    let resolution = resolution.ok_or_else(|| {
+        tracing::error!("Failed to resolve stream URL");
+        StatusCode::BAD_GATEWAY
+    })?;
+
+    try_fetch_and_proxy(&state, &resolution.url, range_header, resolution.content_length).await
+}
+
+// Returns the cached resolution if it's still usable as-is, revalidating it
+// against upstream first when it was cached with `Cache-Control: no-cache`.
+async fn use_cached_resolution(
+    state: &AppState,
+    cache_key: &str,
+    entry: CachedEntry,
+) -> Option<CachedResolution> {
+    if !entry.resolution.must_revalidate {
+        return Some(entry.resolution);
+    }
+
+    tracing::debug!("Revalidating cached resolution for key: {}", cache_key);
+
+    // Revalidate against the actual resolved stream URL, not the Torrentio
+    // entry point: the ETag/Last-Modified we're sending were captured from
+    // this URL's response, so it's the only origin that can honor them.
+    let mut req = state.http_client.head(&entry.resolution.url);
+    if let Some(etag) = &entry.resolution.etag {
+        req = req.header(header::IF_NONE_MATCH, etag);
+    }
+    if let Some(last_modified) = &entry.resolution.last_modified {
+        req = req.header(header::IF_MODIFIED_SINCE, last_modified);
+    }
+
+    let response = req.send().await.ok()?;
+    telemetry::record_upstream_head(response.status());
+
+    if response.status() == StatusCode::NOT_MODIFIED {
+        // Still valid: reset the TTL using this response's caching headers (or
+        // the prior entry's TTL if upstream gave no fresh hint).
+        let has_fresh_hint = response.headers().contains_key(header::CACHE_CONTROL)
+            || response.headers().contains_key(header::EXPIRES);
+        let (ttl, no_store, must_revalidate) = if has_fresh_hint {
+            compute_cache_ttl(response.headers())
+        } else {
+            (entry.ttl, false, entry.resolution.must_revalidate)
+        };
+        if !no_store {
+            state
+                .resolved_url_cache
+                .insert(
+                    cache_key.to_string(),
+                    CachedEntry {
+                        resolution: CachedResolution {
+                            must_revalidate,
+                            ..entry.resolution.clone()
+                        },
+                        ttl,
+                    },
+                )
+                .await;
+        } else {
+            state.resolved_url_cache.invalidate(cache_key).await;
+        }
+        return Some(entry.resolution);
+    }
 
-    try_fetch_and_proxy(&state, &new_url, range_header, true).await
+    // Upstream no longer confirms the cached URL; fall through to a full resolve.
+    state.resolved_url_cache.invalidate(cache_key).await;
+    None
+}
+
+// Resolve a `Location` header value against the URL it was received from,
+// per RFC 3986 (absolute URLs, protocol-relative `//host/...`, absolute-path
+// `/...`, and document-relative references are all handled by `Url::join`).
+fn resolve_redirect_location(current_url: &str, location: &str) -> Option<String> {
+    let base = Url::parse(current_url).ok()?;
+    base.join(location).ok().map(|u| u.into())
 }
 
 async fn resolve_rd_url(
     state: &AppState,
     torrentio_url: &str,
     cache_key: &str,
-) -> Option<String> {
-    tracing::debug!("Resolving redirect: {}", torrentio_url);
+) -> Option<CachedResolution> {
+    let mut current_url = torrentio_url.to_string();
+
+    for hop in 0..MAX_REDIRECTS {
+        tracing::debug!(
+            "Resolving redirect (hop {}/{}): {}",
+            hop + 1,
+            MAX_REDIRECTS,
+            current_url
+        );
 
-    let response = state
-        .http_client
-        .head(torrentio_url)
-        .send()
-        .await
-        .ok()?;
+        let response = state.http_client.head(&current_url).send().await.ok()?;
+        telemetry::record_upstream_head(response.status());
 
-    let redirected_url = response
-        .headers()
-        .get(header::LOCATION)
+        if response.status().is_redirection() {
+            let location = response
+                .headers()
+                .get(header::LOCATION)
+                .and_then(|v| v.to_str().ok())?;
+
+            current_url = resolve_redirect_location(&current_url, location)?;
+            continue;
+        }
+
+        if response.status().is_success() {
+            // No more hops: `current_url` is the final resolved stream URL.
+            return Some(cache_resolution(state, cache_key, current_url, response.headers()).await);
+        }
+
+        // A terminal non-redirect, non-success status (404/403/500/...) means
+        // the chain is broken; don't cache it as if it were a good resolution.
+        tracing::error!(
+            "Resolving {} failed with upstream status {}",
+            current_url,
+            response.status()
+        );
+        return None;
+    }
+
+    tracing::error!(
+        "Exceeded {} redirect hops resolving {}",
+        MAX_REDIRECTS,
+        torrentio_url
+    );
+    None
+}
+
+// Cache the final resolved URL using the caching headers of the terminal
+// response, then return the resolution for the caller to proxy.
+async fn cache_resolution(
+    state: &AppState,
+    cache_key: &str,
+    resolved_url: String,
+    headers: &HeaderMap,
+) -> CachedResolution {
+    let (ttl, no_store, must_revalidate) = compute_cache_ttl(headers);
+
+    let content_length = headers
+        .get(header::CONTENT_LENGTH)
         .and_then(|v| v.to_str().ok())
-        .map(|s| s.to_string())?;
+        .and_then(|v| v.parse().ok());
+
+    let resolution = CachedResolution {
+        url: resolved_url,
+        etag: headers
+            .get(header::ETAG)
+            .and_then(|v| v.to_str().ok())
+            .map(str::to_string),
+        last_modified: headers
+            .get(header::LAST_MODIFIED)
+            .and_then(|v| v.to_str().ok())
+            .map(str::to_string),
+        must_revalidate,
+        content_length,
+    };
+
+    if no_store {
+        tracing::debug!("Upstream sent no-store; skipping cache for key: {}", cache_key);
+        return resolution;
+    }
+
+    tracing::debug!(
+        "Caching redirect for key: {} (ttl: {:?}, revalidate: {})",
+        cache_key,
+        ttl,
+        must_revalidate
+    );
 
-    tracing::debug!("Caching redirect for key: {}", cache_key);
     state
         .resolved_url_cache
-        .insert(cache_key.to_string(), redirected_url.clone())
+        .insert(
+            cache_key.to_string(),
+            CachedEntry {
+                resolution: resolution.clone(),
+                ttl,
+            },
+        )
         .await;
 
-    Some(redirected_url)
+    resolution
 }
 
 async fn try_fetch_and_proxy(
     state: &AppState,
     url: &str,
     range_header: Option<String>,
-    _is_retry: bool,
+    content_length: Option<u64>,
 ) -> Result<Response, StatusCode> {
+    let started_at = Instant::now();
+    let result = try_fetch_and_proxy_inner(state, url, range_header, content_length).await;
+    telemetry::record_fetch_duration(started_at.elapsed());
+    result
+}
+
+async fn try_fetch_and_proxy_inner(
+    state: &AppState,
+    url: &str,
+    range_header: Option<String>,
+    content_length: Option<u64>,
+) -> Result<Response, StatusCode> {
+    // Only validate the range ourselves when we know the total length; otherwise
+    // fall back to forwarding whatever the client sent and trusting upstream.
+    let resolved_range = match (&range_header, content_length) {
+        (Some(raw), Some(total_len)) => match range::parse_range_header(raw) {
+            Some(spec) => match range::resolve_range(spec, total_len) {
+                Ok(resolved) => Some(resolved),
+                Err(_) => {
+                    let mut headers = HeaderMap::new();
+                    headers.insert(
+                        header::CONTENT_RANGE,
+                        range::unsatisfiable_content_range(total_len),
+                    );
+                    headers.insert(
+                        header::ACCEPT_RANGES,
+                        HeaderValue::from_static(range::ACCEPT_RANGES_BYTES),
+                    );
+                    return Ok((StatusCode::RANGE_NOT_SATISFIABLE, headers).into_response());
+                }
+            },
+            None => None, // Malformed Range header: ignore it, serve the full resource.
+        },
+        _ => None,
+    };
+
     let mut req_builder = state.http_client.get(url);
 
-    if let Some(range) = range_header {
+    if let Some(range) = resolved_range {
+        req_builder = req_builder.header(header::RANGE, format!("bytes={}-{}", range.start, range.end));
+    } else if let (Some(range), None) = (&range_header, content_length) {
         req_builder = req_builder.header(header::RANGE, range);
     }
 
@@ -573,14 +999,15 @@ async fn try_fetch_and_proxy(
             StatusCode::BAD_GATEWAY
         })?;
 
-    let status = proxy_resp.status();
+    let upstream_status = proxy_resp.status();
+    telemetry::record_upstream_get(upstream_status);
 
-    if status == StatusCode::NOT_FOUND {
+    if upstream_status == StatusCode::NOT_FOUND {
         return Err(StatusCode::NOT_FOUND);
     }
 
-    if !proxy_resp.status().is_success() {
-        tracing::error!("Remote fetch failed ({}): {}", url, status);
+    if !upstream_status.is_success() {
+        tracing::error!("Remote fetch failed ({}): {}", url, upstream_status);
         return Err(StatusCode::BAD_GATEWAY);
     }
 
@@ -591,19 +1018,43 @@ async fn try_fetch_and_proxy(
             response_headers.insert(key.clone(), value);
         }
     }
+    response_headers.insert(
+        header::ACCEPT_RANGES,
+        HeaderValue::from_static(range::ACCEPT_RANGES_BYTES),
+    );
 
-    // Stream the response body with idle timeout
-    let stream = proxy_resp.bytes_stream();
-
-    // Add idle timeout wrapper to prevent hanging connections
-    let stream_with_timeout = stream.map(move |result| {
-        result.map_err(|e| {
-            tracing::error!("Stream error: {}", e);
-            std::io::Error::other(e)
-        })
-    });
-
-    let body = axum::body::Body::from_stream(stream_with_timeout);
+    let stream: std::pin::Pin<Box<dyn Stream<Item = Result<bytes::Bytes, std::io::Error>> + Send>> =
+        Box::pin(proxy_resp.bytes_stream().map(move |result| match result {
+            Ok(chunk) => {
+                telemetry::record_bytes_streamed(chunk.len() as u64);
+                Ok(chunk)
+            }
+            Err(e) => {
+                tracing::error!("Stream error: {}", e);
+                Err(std::io::Error::other(e))
+            }
+        }));
+
+    // If the client asked for a range but upstream answered with the full body
+    // instead of honoring it, synthesize the 206 ourselves from the full stream.
+    let (status, body) = match resolved_range {
+        Some(range) if upstream_status == StatusCode::OK => {
+            response_headers.insert(
+                header::CONTENT_RANGE,
+                HeaderValue::from_str(&range.content_range_header())
+                    .unwrap_or_else(|_| HeaderValue::from_static("")),
+            );
+            response_headers.insert(
+                header::CONTENT_LENGTH,
+                HeaderValue::from_str(&range.len().to_string()).unwrap_or_else(|_| HeaderValue::from_static("0")),
+            );
+            (
+                StatusCode::PARTIAL_CONTENT,
+                axum::body::Body::from_stream(range::slice_stream(stream, range)),
+            )
+        }
+        _ => (upstream_status, axum::body::Body::from_stream(stream)),
+    };
 
     Ok((status, response_headers, body).into_response())
 }
@@ -654,4 +1105,128 @@ mod tests {
         // Invalid domain
         assert!(validate_torrentio_url("https://evil.com/config/manifest.json").is_err());
     }
+
+    fn headers_with(pairs: &[(header::HeaderName, &str)]) -> HeaderMap {
+        let mut headers = HeaderMap::new();
+        for (name, value) in pairs {
+            headers.insert(name.clone(), HeaderValue::from_str(value).unwrap());
+        }
+        headers
+    }
+
+    #[test]
+    fn test_parse_cache_control_max_age() {
+        let directives = parse_cache_control("max-age=120");
+        assert_eq!(directives.max_age, Some(Duration::from_secs(120)));
+        assert!(!directives.no_store);
+        assert!(!directives.no_cache);
+    }
+
+    #[test]
+    fn test_parse_cache_control_no_store_and_no_cache() {
+        let directives = parse_cache_control("no-store, no-cache");
+        assert!(directives.no_store);
+        assert!(directives.no_cache);
+    }
+
+    #[test]
+    fn test_compute_cache_ttl_no_store_wins_over_max_age() {
+        let headers = headers_with(&[(header::CACHE_CONTROL, "no-store, max-age=120")]);
+        let (ttl, no_store, must_revalidate) = compute_cache_ttl(&headers);
+        assert_eq!(ttl, Duration::from_secs(0));
+        assert!(no_store);
+        assert!(!must_revalidate);
+    }
+
+    #[test]
+    fn test_compute_cache_ttl_max_age_takes_precedence_over_expires() {
+        let future = httpdate::fmt_http_date(SystemTime::now() + Duration::from_secs(9999));
+        let headers = headers_with(&[
+            (header::CACHE_CONTROL, "max-age=60"),
+            (header::EXPIRES, &future),
+        ]);
+        let (ttl, no_store, must_revalidate) = compute_cache_ttl(&headers);
+        assert_eq!(ttl, Duration::from_secs(60));
+        assert!(!no_store);
+        assert!(!must_revalidate);
+    }
+
+    #[test]
+    fn test_compute_cache_ttl_no_cache_sets_must_revalidate() {
+        let headers = headers_with(&[(header::CACHE_CONTROL, "no-cache")]);
+        let (ttl, no_store, must_revalidate) = compute_cache_ttl(&headers);
+        assert_eq!(ttl, CACHE_TTL);
+        assert!(!no_store);
+        assert!(must_revalidate);
+    }
+
+    #[test]
+    fn test_compute_cache_ttl_falls_back_to_expires() {
+        let future = httpdate::fmt_http_date(SystemTime::now() + Duration::from_secs(300));
+        let headers = headers_with(&[(header::EXPIRES, &future)]);
+        let (ttl, no_store, must_revalidate) = compute_cache_ttl(&headers);
+        // Allow a little slack for the time elapsed formatting/parsing the date.
+        assert!(ttl <= Duration::from_secs(300) && ttl > Duration::from_secs(290));
+        assert!(!no_store);
+        assert!(!must_revalidate);
+    }
+
+    #[test]
+    fn test_compute_cache_ttl_already_expired_expires_header() {
+        let past = httpdate::fmt_http_date(SystemTime::now() - Duration::from_secs(60));
+        let headers = headers_with(&[(header::EXPIRES, &past)]);
+        let (ttl, no_store, must_revalidate) = compute_cache_ttl(&headers);
+        assert_eq!(ttl, Duration::from_secs(0));
+        assert!(no_store);
+        assert!(!must_revalidate);
+    }
+
+    #[test]
+    fn test_compute_cache_ttl_no_hints_uses_default() {
+        let (ttl, no_store, must_revalidate) = compute_cache_ttl(&HeaderMap::new());
+        assert_eq!(ttl, CACHE_TTL);
+        assert!(!no_store);
+        assert!(!must_revalidate);
+    }
+
+    #[test]
+    fn test_resolve_redirect_location_absolute() {
+        let resolved = resolve_redirect_location(
+            "https://torrentio.strem.fun/resolve/a",
+            "https://real-debrid.com/download/b",
+        );
+        assert_eq!(resolved.as_deref(), Some("https://real-debrid.com/download/b"));
+    }
+
+    #[test]
+    fn test_resolve_redirect_location_protocol_relative() {
+        let resolved = resolve_redirect_location(
+            "https://torrentio.strem.fun/resolve/a",
+            "//real-debrid.com/download/b",
+        );
+        assert_eq!(resolved.as_deref(), Some("https://real-debrid.com/download/b"));
+    }
+
+    #[test]
+    fn test_resolve_redirect_location_absolute_path() {
+        let resolved = resolve_redirect_location(
+            "https://torrentio.strem.fun/resolve/a",
+            "/other/path",
+        );
+        assert_eq!(resolved.as_deref(), Some("https://torrentio.strem.fun/other/path"));
+    }
+
+    #[test]
+    fn test_resolve_redirect_location_document_relative() {
+        let resolved = resolve_redirect_location(
+            "https://torrentio.strem.fun/resolve/a/",
+            "b",
+        );
+        assert_eq!(resolved.as_deref(), Some("https://torrentio.strem.fun/resolve/a/b"));
+    }
+
+    #[test]
+    fn test_resolve_redirect_location_invalid_base() {
+        assert_eq!(resolve_redirect_location("not a url", "/other"), None);
+    }
 }
@@ -0,0 +1,428 @@
+//! PROXY protocol v1/v2 decoding (HAProxy's PROXY protocol spec).
+//!
+//! When this proxy sits behind an L4 load balancer or TLS terminator, every
+//! inbound TCP connection appears to come from the balancer's address, which
+//! defeats per-client logging. If `PROXY_PROTOCOL=v1` or `v2` is configured,
+//! [`ProxyProtocolListener`] decodes the PROXY header prepended to each
+//! accepted connection and recovers the real client address before handing
+//! the stream to axum. Connections with a malformed header are rejected;
+//! the spec-legal `UNKNOWN` (v1) / `LOCAL` (v2) headers used by load
+//! balancers for their own health checks are accepted, falling back to the
+//! TCP-level peer address since they carry no real client address.
+//!
+//! `axum::serve` awaits `Listener::accept` serially, so header decoding must
+//! never happen inline there: a slow or malicious client stalling on its
+//! header would block acceptance of every other connection. Instead a
+//! background task does the raw `TcpListener::accept` loop and spawns one
+//! timeout-bounded decode task per connection, handing decoded streams back
+//! through an mpsc channel that `Listener::accept` merely receives from.
+
+use std::io;
+use std::net::{IpAddr, Ipv4Addr, Ipv6Addr, SocketAddr};
+use std::pin::Pin;
+use std::str::FromStr;
+use std::task::{Context, Poll};
+use std::time::Duration;
+
+use axum::extract::connect_info::Connected;
+use axum::serve::{IncomingStream, Listener};
+use tokio::io::{AsyncRead, AsyncReadExt, AsyncWrite, ReadBuf};
+use tokio::net::{TcpListener, TcpStream};
+use tokio::sync::mpsc;
+
+/// Longest a PROXY v1 header line may be per spec (including the trailing CRLF).
+const V1_MAX_HEADER_LEN: usize = 107;
+/// Max time to wait for a full PROXY header before dropping the connection.
+/// Each decode runs in its own spawned task, so this only bounds how long
+/// that one task lingers — it can never block acceptance of other clients.
+const HEADER_READ_TIMEOUT: Duration = Duration::from_secs(5);
+const V2_SIGNATURE: [u8; 12] = [
+    0x0D, 0x0A, 0x0D, 0x0A, 0x00, 0x0D, 0x0A, 0x51, 0x55, 0x49, 0x54, 0x0A,
+];
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) enum ProxyProtocolMode {
+    V1,
+    V2,
+}
+
+impl FromStr for ProxyProtocolMode {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_ascii_lowercase().as_str() {
+            "v1" => Ok(Self::V1),
+            "v2" => Ok(Self::V2),
+            other => Err(anyhow::anyhow!(
+                "Unknown PROXY_PROTOCOL mode '{}' (expected 'v1' or 'v2')",
+                other
+            )),
+        }
+    }
+}
+
+/// The real client address recovered from a PROXY protocol header, exposed to
+/// handlers/middleware via axum's `ConnectInfo` extractor.
+#[derive(Debug, Clone, Copy)]
+pub(crate) struct RealClientAddr(pub SocketAddr);
+
+impl Connected<IncomingStream<'_, ProxyProtocolListener>> for RealClientAddr {
+    fn connect_info(stream: IncomingStream<'_, ProxyProtocolListener>) -> Self {
+        RealClientAddr(stream.io().real_addr)
+    }
+}
+
+/// A TCP stream with the header already stripped off and the real client
+/// address recovered from it.
+pub(crate) struct ProxyProtocolStream {
+    inner: TcpStream,
+    real_addr: SocketAddr,
+}
+
+impl AsyncRead for ProxyProtocolStream {
+    fn poll_read(
+        mut self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+        buf: &mut ReadBuf<'_>,
+    ) -> Poll<io::Result<()>> {
+        Pin::new(&mut self.inner).poll_read(cx, buf)
+    }
+}
+
+impl AsyncWrite for ProxyProtocolStream {
+    fn poll_write(mut self: Pin<&mut Self>, cx: &mut Context<'_>, buf: &[u8]) -> Poll<io::Result<usize>> {
+        Pin::new(&mut self.inner).poll_write(cx, buf)
+    }
+
+    fn poll_flush(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<io::Result<()>> {
+        Pin::new(&mut self.inner).poll_flush(cx)
+    }
+
+    fn poll_shutdown(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<io::Result<()>> {
+        Pin::new(&mut self.inner).poll_shutdown(cx)
+    }
+}
+
+/// How many decoded connections may queue up waiting for axum to call
+/// `accept()` again. Bounded so a slow consumer can't let the background
+/// accept loop pile up unbounded memory; it never blocks raw TCP acceptance
+/// itself, only how many fully-decoded streams sit waiting.
+const ACCEPTED_QUEUE_SIZE: usize = 64;
+
+/// Wraps a plain `TcpListener`, decoding a PROXY protocol header off every
+/// accepted connection before it's handed to axum. The raw accept loop and
+/// header decoding run in a background task so a slow/malformed client can
+/// never stall acceptance of other connections (see module docs).
+pub(crate) struct ProxyProtocolListener {
+    accepted: mpsc::Receiver<(ProxyProtocolStream, SocketAddr)>,
+    local_addr: SocketAddr,
+}
+
+impl ProxyProtocolListener {
+    pub(crate) fn new(inner: TcpListener, mode: ProxyProtocolMode) -> Self {
+        let local_addr = inner
+            .local_addr()
+            .expect("TcpListener should have a local address after a successful bind");
+
+        let (tx, rx) = mpsc::channel(ACCEPTED_QUEUE_SIZE);
+        tokio::spawn(accept_loop(inner, mode, tx));
+
+        Self { accepted: rx, local_addr }
+    }
+}
+
+/// Runs forever: accepts raw TCP connections and spawns a timeout-bounded
+/// decode task per connection, so one stalled/malicious client only ever
+/// occupies its own task, never the shared accept loop.
+async fn accept_loop(inner: TcpListener, mode: ProxyProtocolMode, tx: mpsc::Sender<(ProxyProtocolStream, SocketAddr)>) {
+    loop {
+        let (stream, peer_addr) = match inner.accept().await {
+            Ok(pair) => pair,
+            Err(e) => {
+                tracing::warn!("Failed to accept TCP connection: {}", e);
+                continue;
+            }
+        };
+
+        let tx = tx.clone();
+        tokio::spawn(async move {
+            match tokio::time::timeout(HEADER_READ_TIMEOUT, decode_header(stream, mode)).await {
+                Ok(Ok((stream, real_addr))) => {
+                    let real_addr = real_addr.unwrap_or(peer_addr);
+                    let _ = tx.send((ProxyProtocolStream { inner: stream, real_addr }, peer_addr)).await;
+                }
+                Ok(Err(e)) => {
+                    tracing::warn!(
+                        "Rejecting connection from {}: malformed PROXY protocol header: {}",
+                        peer_addr,
+                        e
+                    );
+                }
+                Err(_) => {
+                    tracing::warn!(
+                        "Rejecting connection from {}: timed out waiting for PROXY protocol header",
+                        peer_addr
+                    );
+                }
+            }
+        });
+    }
+}
+
+impl Listener for ProxyProtocolListener {
+    type Io = ProxyProtocolStream;
+    type Addr = SocketAddr;
+
+    async fn accept(&mut self) -> (Self::Io, Self::Addr) {
+        match self.accepted.recv().await {
+            Some(pair) => pair,
+            // The accept-loop task never exits on its own; reaching this
+            // means it panicked. There's nothing more we can accept.
+            None => std::future::pending().await,
+        }
+    }
+
+    fn local_addr(&self) -> io::Result<Self::Addr> {
+        Ok(self.local_addr)
+    }
+}
+
+/// Decodes the PROXY header off `stream`. The returned `SocketAddr` is
+/// `None` for the spec-legal `UNKNOWN` (v1) / `LOCAL` (v2) headers, which
+/// carry no real client address; callers should fall back to the TCP-level
+/// peer address in that case.
+async fn decode_header(
+    mut stream: TcpStream,
+    mode: ProxyProtocolMode,
+) -> io::Result<(TcpStream, Option<SocketAddr>)> {
+    let addr = match mode {
+        ProxyProtocolMode::V1 => decode_v1(&mut stream).await?,
+        ProxyProtocolMode::V2 => decode_v2(&mut stream).await?,
+    };
+    Ok((stream, addr))
+}
+
+async fn decode_v1<R: AsyncRead + Unpin>(stream: &mut R) -> io::Result<Option<SocketAddr>> {
+    let mut line = Vec::with_capacity(V1_MAX_HEADER_LEN);
+    let mut byte = [0u8; 1];
+
+    loop {
+        stream.read_exact(&mut byte).await?;
+        line.push(byte[0]);
+
+        if line.ends_with(b"\r\n") {
+            break;
+        }
+
+        if line.len() > V1_MAX_HEADER_LEN {
+            return Err(invalid_data("PROXY v1 header exceeds maximum length"));
+        }
+    }
+
+    let line = std::str::from_utf8(&line)
+        .map_err(|_| invalid_data("PROXY v1 header is not valid UTF-8"))?
+        .trim_end_matches("\r\n");
+
+    let mut parts = line.split(' ');
+
+    if parts.next() != Some("PROXY") {
+        return Err(invalid_data("PROXY v1 header missing 'PROXY' prefix"));
+    }
+
+    match parts.next() {
+        // Spec-legal health-check form from the balancer itself: well-formed,
+        // just carries no client address to recover.
+        Some("UNKNOWN") => Ok(None),
+        Some("TCP4") | Some("TCP6") => {
+            let src_ip: IpAddr = parts
+                .next()
+                .ok_or_else(|| invalid_data("PROXY v1 header missing source address"))?
+                .parse()
+                .map_err(|_| invalid_data("PROXY v1 header has an invalid source address"))?;
+            let _dst_ip = parts.next();
+            let src_port: u16 = parts
+                .next()
+                .ok_or_else(|| invalid_data("PROXY v1 header missing source port"))?
+                .parse()
+                .map_err(|_| invalid_data("PROXY v1 header has an invalid source port"))?;
+
+            Ok(Some(SocketAddr::new(src_ip, src_port)))
+        }
+        _ => Err(invalid_data("PROXY v1 header has an unsupported protocol")),
+    }
+}
+
+async fn decode_v2<R: AsyncRead + Unpin>(stream: &mut R) -> io::Result<Option<SocketAddr>> {
+    let mut signature = [0u8; 12];
+    stream.read_exact(&mut signature).await?;
+    if signature != V2_SIGNATURE {
+        return Err(invalid_data("PROXY v2 signature mismatch"));
+    }
+
+    let mut ver_cmd_fam_len = [0u8; 4];
+    stream.read_exact(&mut ver_cmd_fam_len).await?;
+
+    let version = ver_cmd_fam_len[0] >> 4;
+    if version != 2 {
+        return Err(invalid_data("unsupported PROXY protocol version"));
+    }
+    let command = ver_cmd_fam_len[0] & 0x0F;
+    let family = ver_cmd_fam_len[1] >> 4;
+    let len = u16::from_be_bytes([ver_cmd_fam_len[2], ver_cmd_fam_len[3]]) as usize;
+
+    let mut address_block = vec![0u8; len];
+    stream.read_exact(&mut address_block).await?;
+
+    // LOCAL command (health checks from the balancer itself): spec-legal,
+    // just carries no real client address to recover.
+    if command == 0x0 {
+        return Ok(None);
+    }
+
+    match family {
+        0x1 if address_block.len() >= 12 => {
+            // AF_INET: 4 bytes src addr, 4 bytes dst addr, 2+2 bytes ports follow
+            let src = Ipv4Addr::new(address_block[0], address_block[1], address_block[2], address_block[3]);
+            let src_port = u16::from_be_bytes([address_block[8], address_block[9]]);
+            Ok(Some(SocketAddr::new(IpAddr::V4(src), src_port)))
+        }
+        0x2 if address_block.len() >= 36 => {
+            // AF_INET6: 16 bytes src addr, 16 bytes dst addr, then ports
+            let mut octets = [0u8; 16];
+            octets.copy_from_slice(&address_block[0..16]);
+            let src = Ipv6Addr::from(octets);
+            let src_port = u16::from_be_bytes([address_block[32], address_block[33]]);
+            Ok(Some(SocketAddr::new(IpAddr::V6(src), src_port)))
+        }
+        _ => Err(invalid_data("PROXY v2 header has an unsupported/unknown address family")),
+    }
+}
+
+fn invalid_data(msg: &str) -> io::Error {
+    io::Error::new(io::ErrorKind::InvalidData, msg.to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tokio::io::AsyncWriteExt;
+
+    #[test]
+    fn parses_mode_from_str() {
+        assert_eq!(ProxyProtocolMode::from_str("v1").unwrap(), ProxyProtocolMode::V1);
+        assert_eq!(ProxyProtocolMode::from_str("V2").unwrap(), ProxyProtocolMode::V2);
+        assert!(ProxyProtocolMode::from_str("v3").is_err());
+    }
+
+    /// Feeds `bytes` through a duplex stream and decodes it with `decode`,
+    /// standing in for a real `TcpStream` so header parsing can be tested
+    /// without an actual socket.
+    async fn decode_bytes<F, Fut>(bytes: &[u8], decode: F) -> io::Result<Option<SocketAddr>>
+    where
+        F: FnOnce(tokio::io::DuplexStream) -> Fut,
+        Fut: std::future::Future<Output = io::Result<Option<SocketAddr>>>,
+    {
+        let (mut writer, reader) = tokio::io::duplex(1024);
+        writer.write_all(bytes).await.unwrap();
+        drop(writer);
+        decode(reader).await
+    }
+
+    fn v2_header(command: u8, family_proto: u8, address_block: &[u8]) -> Vec<u8> {
+        let mut header = V2_SIGNATURE.to_vec();
+        header.push(0x20 | command); // version 2, given command
+        header.push(family_proto);
+        header.extend_from_slice(&(address_block.len() as u16).to_be_bytes());
+        header.extend_from_slice(address_block);
+        header
+    }
+
+    #[tokio::test]
+    async fn decode_v1_parses_tcp4_header() {
+        let addr = decode_bytes(b"PROXY TCP4 192.168.1.1 192.168.1.2 5555 443\r\n", |mut r| async move {
+            decode_v1(&mut r).await
+        })
+        .await
+        .unwrap();
+        assert_eq!(addr, Some("192.168.1.1:5555".parse().unwrap()));
+    }
+
+    #[tokio::test]
+    async fn decode_v1_parses_tcp6_header() {
+        let addr = decode_bytes(b"PROXY TCP6 ::1 ::2 1234 443\r\n", |mut r| async move {
+            decode_v1(&mut r).await
+        })
+        .await
+        .unwrap();
+        assert_eq!(addr, Some("[::1]:1234".parse().unwrap()));
+    }
+
+    #[tokio::test]
+    async fn decode_v1_accepts_unknown_with_no_address() {
+        let addr = decode_bytes(b"PROXY UNKNOWN\r\n", |mut r| async move { decode_v1(&mut r).await })
+            .await
+            .unwrap();
+        assert_eq!(addr, None);
+    }
+
+    #[tokio::test]
+    async fn decode_v1_rejects_missing_prefix() {
+        let result = decode_bytes(b"HELLO TCP4 1.1.1.1 2.2.2.2 1 2\r\n", |mut r| async move {
+            decode_v1(&mut r).await
+        })
+        .await;
+        assert!(result.is_err());
+    }
+
+    #[tokio::test]
+    async fn decode_v1_rejects_invalid_source_address() {
+        let result = decode_bytes(b"PROXY TCP4 not-an-ip 192.168.1.2 5555 443\r\n", |mut r| async move {
+            decode_v1(&mut r).await
+        })
+        .await;
+        assert!(result.is_err());
+    }
+
+    #[tokio::test]
+    async fn decode_v2_parses_af_inet_header() {
+        let mut address_block = Vec::new();
+        address_block.extend_from_slice(&[127, 0, 0, 1]); // src
+        address_block.extend_from_slice(&[10, 0, 0, 1]); // dst
+        address_block.extend_from_slice(&4444u16.to_be_bytes()); // src port
+        address_block.extend_from_slice(&80u16.to_be_bytes()); // dst port
+        let header = v2_header(0x1, 0x11, &address_block);
+
+        let addr = decode_bytes(&header, |mut r| async move { decode_v2(&mut r).await })
+            .await
+            .unwrap();
+        assert_eq!(addr, Some("127.0.0.1:4444".parse().unwrap()));
+    }
+
+    #[tokio::test]
+    async fn decode_v2_accepts_local_command_with_no_address() {
+        let header = v2_header(0x0, 0x00, &[]);
+
+        let addr = decode_bytes(&header, |mut r| async move { decode_v2(&mut r).await })
+            .await
+            .unwrap();
+        assert_eq!(addr, None);
+    }
+
+    #[tokio::test]
+    async fn decode_v2_rejects_bad_signature() {
+        let mut header = vec![0u8; 12];
+        header.extend_from_slice(&[0x21, 0x11, 0, 12]);
+        header.extend_from_slice(&[0u8; 12]);
+
+        let result = decode_bytes(&header, |mut r| async move { decode_v2(&mut r).await }).await;
+        assert!(result.is_err());
+    }
+
+    #[tokio::test]
+    async fn decode_v2_rejects_unknown_family() {
+        let header = v2_header(0x1, 0x30, &[0u8; 4]);
+
+        let result = decode_bytes(&header, |mut r| async move { decode_v2(&mut r).await }).await;
+        assert!(result.is_err());
+    }
+}
@@ -0,0 +1,227 @@
+//! HTTP Range request handling (RFC 7233).
+//!
+//! Forwarding whatever `Range` header the client sent and trusting upstream
+//! to answer correctly breaks seeking when upstream ignores
+//! the range or returns a full `200` when a `206 Partial Content` was
+//! expected. This module parses and validates the client's range against a
+//! known total content length, and can synthesize the correct partial byte
+//! stream from a full body when upstream didn't honor the range itself.
+
+use axum::http::{HeaderValue, StatusCode};
+use bytes::Bytes;
+use futures::{Stream, StreamExt};
+
+pub(crate) const ACCEPT_RANGES_BYTES: &str = "bytes";
+
+/// A single client-requested byte range, not yet validated against a total length.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) enum RangeSpec {
+    /// `bytes=start-end` or open-ended `bytes=start-`
+    FromStart { start: u64, end: Option<u64> },
+    /// `bytes=-N` — the last `N` bytes of the resource
+    Suffix { length: u64 },
+}
+
+/// A range validated against a known total content length.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) struct ResolvedRange {
+    pub start: u64,
+    pub end: u64, // inclusive
+    pub total_len: u64,
+}
+
+impl ResolvedRange {
+    pub fn len(&self) -> u64 {
+        self.end - self.start + 1
+    }
+
+    pub fn content_range_header(&self) -> String {
+        format!("bytes {}-{}/{}", self.start, self.end, self.total_len)
+    }
+}
+
+/// Parse a `Range` header value. Only the single-range form is supported
+/// (`bytes=start-end`, `bytes=start-`, `bytes=-suffix_length`); multi-range
+/// requests, a non-`bytes` unit, or malformed numbers all return `None` so
+/// the caller can fall back to serving the full resource, per RFC 7233 §3.1
+/// ("a server ... MAY ignore the Range header field").
+pub(crate) fn parse_range_header(value: &str) -> Option<RangeSpec> {
+    let spec = value.trim().strip_prefix("bytes=")?;
+
+    if spec.contains(',') {
+        return None;
+    }
+
+    let (start_str, end_str) = spec.split_once('-')?;
+
+    if start_str.is_empty() {
+        let length: u64 = end_str.parse().ok()?;
+        return Some(RangeSpec::Suffix { length });
+    }
+
+    let start: u64 = start_str.parse().ok()?;
+    let end = if end_str.is_empty() {
+        None
+    } else {
+        Some(end_str.parse().ok()?)
+    };
+
+    Some(RangeSpec::FromStart { start, end })
+}
+
+/// Resolve a parsed range against the resource's total length.
+pub(crate) fn resolve_range(spec: RangeSpec, total_len: u64) -> Result<ResolvedRange, StatusCode> {
+    if total_len == 0 {
+        return Err(StatusCode::RANGE_NOT_SATISFIABLE);
+    }
+
+    let (start, end) = match spec {
+        RangeSpec::Suffix { length } => {
+            if length == 0 {
+                return Err(StatusCode::RANGE_NOT_SATISFIABLE);
+            }
+            let length = length.min(total_len);
+            (total_len - length, total_len - 1)
+        }
+        RangeSpec::FromStart { start, end } => (start, end.unwrap_or(total_len - 1).min(total_len - 1)),
+    };
+
+    if start > end || start >= total_len {
+        return Err(StatusCode::RANGE_NOT_SATISFIABLE);
+    }
+
+    Ok(ResolvedRange { start, end, total_len })
+}
+
+/// `Content-Range: bytes */<total>` header value for a `416` response.
+pub(crate) fn unsatisfiable_content_range(total_len: u64) -> HeaderValue {
+    HeaderValue::from_str(&format!("bytes */{}", total_len))
+        .unwrap_or_else(|_| HeaderValue::from_static("bytes */0"))
+}
+
+/// Slice a full-body byte stream down to `range`, for upstreams that returned
+/// `200 OK` (the whole resource) instead of honoring the requested range.
+pub(crate) fn slice_stream<S>(
+    stream: S,
+    range: ResolvedRange,
+) -> impl Stream<Item = Result<Bytes, std::io::Error>>
+where
+    S: Stream<Item = Result<Bytes, std::io::Error>> + Unpin,
+{
+    futures::stream::unfold(
+        (stream, range.start, range.len()),
+        |(mut stream, mut skip, mut remaining)| async move {
+            if remaining == 0 {
+                return None;
+            }
+
+            loop {
+                let chunk = match stream.next().await? {
+                    Ok(chunk) => chunk,
+                    Err(e) => return Some((Err(e), (stream, skip, remaining))),
+                };
+
+                let chunk_len = chunk.len() as u64;
+
+                if skip >= chunk_len {
+                    skip -= chunk_len;
+                    continue;
+                }
+
+                let start = skip as usize;
+                skip = 0;
+
+                let available = chunk_len - start as u64;
+                let take = available.min(remaining) as usize;
+                let sliced = chunk.slice(start..start + take);
+                remaining -= take as u64;
+
+                return Some((Ok(sliced), (stream, skip, remaining)));
+            }
+        },
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parses_full_range() {
+        assert_eq!(
+            parse_range_header("bytes=0-499"),
+            Some(RangeSpec::FromStart { start: 0, end: Some(499) })
+        );
+    }
+
+    #[test]
+    fn parses_open_ended_range() {
+        assert_eq!(
+            parse_range_header("bytes=500-"),
+            Some(RangeSpec::FromStart { start: 500, end: None })
+        );
+    }
+
+    #[test]
+    fn parses_suffix_range() {
+        assert_eq!(parse_range_header("bytes=-500"), Some(RangeSpec::Suffix { length: 500 }));
+    }
+
+    #[test]
+    fn rejects_multi_range() {
+        assert_eq!(parse_range_header("bytes=0-10,20-30"), None);
+    }
+
+    #[test]
+    fn rejects_non_bytes_unit() {
+        assert_eq!(parse_range_header("items=0-10"), None);
+    }
+
+    #[test]
+    fn resolves_range_within_bounds() {
+        let spec = RangeSpec::FromStart { start: 0, end: Some(499) };
+        let resolved = resolve_range(spec, 1000).unwrap();
+        assert_eq!(resolved.start, 0);
+        assert_eq!(resolved.end, 499);
+        assert_eq!(resolved.len(), 500);
+    }
+
+    #[test]
+    fn clamps_end_to_total_len() {
+        let spec = RangeSpec::FromStart { start: 900, end: Some(2000) };
+        let resolved = resolve_range(spec, 1000).unwrap();
+        assert_eq!(resolved.end, 999);
+    }
+
+    #[test]
+    fn suffix_longer_than_total_clamps_to_whole_resource() {
+        let spec = RangeSpec::Suffix { length: 5000 };
+        let resolved = resolve_range(spec, 1000).unwrap();
+        assert_eq!(resolved.start, 0);
+        assert_eq!(resolved.end, 999);
+    }
+
+    #[test]
+    fn rejects_start_past_total_len() {
+        let spec = RangeSpec::FromStart { start: 1000, end: None };
+        assert_eq!(resolve_range(spec, 1000), Err(StatusCode::RANGE_NOT_SATISFIABLE));
+    }
+
+    #[tokio::test]
+    async fn slices_stream_to_range() {
+        let chunks: Vec<Result<Bytes, std::io::Error>> = vec![
+            Ok(Bytes::from_static(b"0123456789")),
+            Ok(Bytes::from_static(b"abcdefghij")),
+        ];
+        let source = futures::stream::iter(chunks);
+        let range = ResolvedRange { start: 8, end: 13, total_len: 20 };
+
+        let sliced: Vec<u8> = slice_stream(source, range)
+            .map(|c| c.unwrap().to_vec())
+            .collect::<Vec<_>>()
+            .await
+            .concat();
+
+        assert_eq!(sliced, b"89abcd");
+    }
+}
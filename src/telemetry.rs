@@ -0,0 +1,49 @@
+//! Prometheus metrics for cache effectiveness, upstream health, and proxy
+//! throughput. Call sites record through the small helpers below rather than
+//! reaching for the `metrics` macros directly, so metric names/labels stay
+//! consistent in one place.
+
+use axum::http::StatusCode;
+use std::time::Duration;
+
+/// Bucket a status code the way operators read dashboards: by class.
+fn status_class(status: StatusCode) -> &'static str {
+    match status.as_u16() {
+        100..=199 => "1xx",
+        200..=299 => "2xx",
+        300..=399 => "3xx",
+        400..=499 => "4xx",
+        _ => "5xx",
+    }
+}
+
+pub(crate) fn record_cache_result(hit: bool) {
+    let result = if hit { "hit" } else { "miss" };
+    metrics::counter!("resolved_url_cache_requests_total", "result" => result).increment(1);
+}
+
+pub(crate) fn record_lock_contention() {
+    metrics::counter!("resolve_lock_contention_total").increment(1);
+}
+
+pub(crate) fn record_upstream_head(status: StatusCode) {
+    metrics::counter!("upstream_requests_total", "method" => "HEAD", "status_class" => status_class(status))
+        .increment(1);
+}
+
+pub(crate) fn record_upstream_get(status: StatusCode) {
+    metrics::counter!("upstream_requests_total", "method" => "GET", "status_class" => status_class(status))
+        .increment(1);
+}
+
+pub(crate) fn record_resolve_duration(elapsed: Duration) {
+    metrics::histogram!("resolve_rd_url_duration_seconds").record(elapsed.as_secs_f64());
+}
+
+pub(crate) fn record_fetch_duration(elapsed: Duration) {
+    metrics::histogram!("try_fetch_and_proxy_duration_seconds").record(elapsed.as_secs_f64());
+}
+
+pub(crate) fn record_bytes_streamed(bytes: u64) {
+    metrics::counter!("proxy_bytes_streamed_total").increment(bytes);
+}